@@ -1,6 +1,9 @@
 use std::{fmt, mem};
+use std::collections::VecDeque;
+use std::io::{self, Read};
 
 use futures::{Stream, Poll, Async};
+use futures::stream::Wait;
 use bytes::{Buf, Bytes};
 use hyper::body::Payload;
 
@@ -12,6 +15,9 @@ pub struct Body {
 enum Inner {
     Reusable(Bytes),
     Hyper(::hyper::Body),
+    Streaming(Box<Stream<Item = Chunk, Error = ::Error> + Send>),
+    Cipher(Box<Body>, ChaCha20),
+    Multi(BufList<Bytes>),
 }
 
 impl Body {
@@ -19,18 +25,21 @@ impl Body {
         match self.inner {
             Inner::Hyper(ref mut body) => return body,
             Inner::Reusable(_) => (),
+            Inner::Streaming(_) => unreachable!("streaming body has no hyper::Body to poll"),
+            Inner::Cipher(..) => unreachable!("cipher body has no hyper::Body to poll"),
+            Inner::Multi(_) => unreachable!("multi body has no hyper::Body to poll"),
         }
 
         let bytes = match mem::replace(&mut self.inner, Inner::Reusable(Bytes::new())) {
             Inner::Reusable(bytes) => bytes,
-            Inner::Hyper(_) => unreachable!(),
+            Inner::Hyper(_) | Inner::Streaming(_) | Inner::Cipher(..) | Inner::Multi(_) => unreachable!(),
         };
 
         self.inner = Inner::Hyper(bytes.into());
 
         match self.inner {
             Inner::Hyper(ref mut body) => return body,
-            Inner::Reusable(_) => unreachable!(),
+            Inner::Reusable(_) | Inner::Streaming(_) | Inner::Cipher(..) | Inner::Multi(_) => unreachable!(),
         }
     }
 
@@ -38,6 +47,94 @@ impl Body {
         match self.inner {
             Inner::Reusable(ref bytes) => Some(bytes.len() as u64),
             Inner::Hyper(ref body) => body.content_length(),
+            Inner::Streaming(_) => None,
+            Inner::Cipher(ref inner, _) => inner.content_length(),
+            Inner::Multi(ref list) => Some(list.remaining() as u64),
+        }
+    }
+
+    /// Wrap a futures `Stream` in a box inside `Body`.
+    ///
+    /// Lets you stream a body incrementally instead of passing a fully
+    /// buffered `Bytes`, `Vec<u8>`, or `String`. The resulting `Body` has
+    /// no known `content_length`, so it will be sent with
+    /// `Transfer-Encoding: chunked`.
+    pub fn wrap_stream<S>(stream: S) -> Body
+    where
+        S: Stream + Send + 'static,
+        S::Item: Into<Chunk>,
+        S::Error: Into<::Error>,
+    {
+        Body {
+            inner: Inner::Streaming(Box::new(stream.map(Into::into).map_err(Into::into))),
+        }
+    }
+
+    /// Encrypt this body with ChaCha20 as it streams, so the ciphertext is
+    /// never fully materialized in memory.
+    ///
+    /// `key` and `nonce` are the usual 256-bit key and 96-bit nonce from
+    /// the IETF ChaCha20 construction (RFC 8439).
+    ///
+    /// # Security
+    ///
+    /// This is the raw ChaCha20 stream cipher only: confidentiality, no
+    /// integrity. There is no Poly1305 (or any other) MAC, so tampered or
+    /// truncated ciphertext will not be detected — pair it with your own
+    /// authentication if that matters for your use case.
+    ///
+    /// Never reuse a `(key, nonce)` pair for more than one body. Two
+    /// ciphertexts produced under the same key and nonce leak their XOR,
+    /// which is generally enough to recover both plaintexts.
+    pub fn encrypt_chacha20(self, key: [u8; 32], nonce: [u8; 12]) -> Body {
+        Body {
+            inner: Inner::Cipher(Box::new(self), ChaCha20::new(key, nonce)),
+        }
+    }
+
+    /// Decrypt a body that was produced by [`encrypt_chacha20`](Body::encrypt_chacha20).
+    ///
+    /// ChaCha20 is a symmetric stream cipher, so decryption is the exact
+    /// same XOR-with-keystream operation as encryption. The same security
+    /// notes on [`encrypt_chacha20`](Body::encrypt_chacha20) apply here:
+    /// no integrity check, and never reuse a `(key, nonce)` pair.
+    pub fn decrypt_chacha20(self, key: [u8; 32], nonce: [u8; 12]) -> Body {
+        self.encrypt_chacha20(key, nonce)
+    }
+
+    /// Build a `Body` out of many `Bytes` segments without copying them
+    /// into one contiguous buffer.
+    ///
+    /// Unlike the `From<Bytes>`/`From<Vec<u8>>` impls, this keeps each
+    /// segment as-is (`Bytes` is reference-counted, so cloning one is
+    /// cheap) and streams them in order.
+    pub fn from_segments<I>(segments: I) -> Body
+    where
+        I: IntoIterator<Item = Bytes>,
+    {
+        Body {
+            inner: Inner::Multi(BufList::new(segments.into_iter().collect())),
+        }
+    }
+
+    /// Turn this `Body` into a blocking `std::io::Read`.
+    ///
+    /// `read()` calls are served from the buffered current `Chunk`; once it
+    /// is drained, the next `read()` blocks the current thread (parking it
+    /// between polls) until the underlying `Stream` yields the next one. Useful
+    /// for feeding a body into APIs that only know `Read`, like
+    /// `std::io::copy` or a synchronous decompressor.
+    ///
+    /// Must not be called from the same thread that drives the runtime
+    /// producing this body's chunks (e.g. from inside a future running on
+    /// that reactor) — since `read()` parks the calling thread until a
+    /// chunk arrives, doing so would leave nothing to poll the connection
+    /// forward and deadlock.
+    pub fn into_reader(self) -> impl Read {
+        Reader {
+            inner: self.wait(),
+            chunk: Chunk::default(),
+            pos: 0,
         }
     }
 
@@ -62,11 +159,38 @@ impl Body {
         }
     }
 
+    /// Consume this `Body`, returning a value to keep around for a retry
+    /// (redirect/connection-failure replay) alongside the `hyper::Body` to
+    /// actually send, if this body is cheap enough to reconstruct that a
+    /// retry is worth supporting.
     #[inline]
-    pub(crate) fn into_hyper(self) -> (Option<Bytes>, ::hyper::Body) {
+    pub(crate) fn into_hyper(self) -> (Option<Body>, ::hyper::Body) {
         match self.inner {
-            Inner::Reusable(chunk) => (Some(chunk.clone()), chunk.into()),
+            Inner::Reusable(chunk) => {
+                let reusable = Body::reusable(chunk.clone());
+                (Some(reusable), chunk.into())
+            }
             Inner::Hyper(b) => (None, b),
+            Inner::Streaming(stream) => (None, ::hyper::Body::wrap_stream(stream)),
+            Inner::Cipher(inner, cipher) => {
+                let body = Body {
+                    inner: Inner::Cipher(inner, cipher),
+                };
+                (None, ::hyper::Body::wrap_stream(body))
+            }
+            Inner::Multi(list) => {
+                // `Bytes` is reference-counted, so cloning the whole list
+                // is just bumping refcounts on each segment — no copying
+                // of the underlying data, unlike collecting it into one
+                // contiguous buffer would be.
+                let reusable = Body {
+                    inner: Inner::Multi(list.clone()),
+                };
+                let body = Body {
+                    inner: Inner::Multi(list),
+                };
+                (Some(reusable), ::hyper::Body::wrap_stream(body))
+            }
         }
     }
 }
@@ -77,6 +201,27 @@ impl Stream for Body {
 
     #[inline]
     fn poll(&mut self) -> Poll<Option<Self::Item>, Self::Error> {
+        match self.inner {
+            Inner::Streaming(ref mut stream) => return stream.poll(),
+            Inner::Cipher(ref mut inner, ref mut cipher) => {
+                return match try_!(inner.poll()) {
+                    Async::Ready(Some(chunk)) => {
+                        let mut bytes = chunk.as_ref().to_vec();
+                        cipher.apply_keystream(&mut bytes);
+                        Ok(Async::Ready(Some(Chunk::from_chunk(bytes.into()))))
+                    }
+                    Async::Ready(None) => Ok(Async::Ready(None)),
+                    Async::NotReady => Ok(Async::NotReady),
+                };
+            }
+            Inner::Multi(ref mut list) => {
+                // `BufList::new` already drops empty segments, so the
+                // front is never a zero-length chunk.
+                return Ok(Async::Ready(list.bufs.pop_front().map(Chunk::from_chunk)));
+            }
+            _ => (),
+        }
+
         match try_!(self.poll_inner().poll()) {
             Async::Ready(opt) => Ok(Async::Ready(opt.map(|chunk| Chunk {
                 inner: chunk,
@@ -143,6 +288,42 @@ impl Chunk {
         }
     }
 }
+
+impl From<Bytes> for Chunk {
+    #[inline]
+    fn from(bytes: Bytes) -> Chunk {
+        Chunk::from_chunk(bytes)
+    }
+}
+
+impl From<Vec<u8>> for Chunk {
+    #[inline]
+    fn from(vec: Vec<u8>) -> Chunk {
+        Chunk::from_chunk(vec.into())
+    }
+}
+
+impl From<&'static [u8]> for Chunk {
+    #[inline]
+    fn from(s: &'static [u8]) -> Chunk {
+        Chunk::from_chunk(Bytes::from_static(s))
+    }
+}
+
+impl From<String> for Chunk {
+    #[inline]
+    fn from(s: String) -> Chunk {
+        Chunk::from_chunk(s.into())
+    }
+}
+
+impl From<&'static str> for Chunk {
+    #[inline]
+    fn from(s: &'static str) -> Chunk {
+        s.as_bytes().into()
+    }
+}
+
 impl Buf for Chunk {
     fn bytes(&self) -> &[u8] {
         self.inner.bytes()
@@ -188,6 +369,196 @@ impl IntoIterator for Chunk {
     }
 }
 
+/// Blocking `Read` adapter returned by `Body::into_reader`.
+struct Reader {
+    inner: Wait<Body>,
+    chunk: Chunk,
+    pos: usize,
+}
+
+impl Read for Reader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        loop {
+            let remaining = &self.chunk[self.pos..];
+            if !remaining.is_empty() {
+                let len = ::std::cmp::min(buf.len(), remaining.len());
+                buf[..len].copy_from_slice(&remaining[..len]);
+                self.pos += len;
+                return Ok(len);
+            }
+
+            match self.inner.next() {
+                Some(Ok(chunk)) => {
+                    self.chunk = chunk;
+                    self.pos = 0;
+                }
+                Some(Err(e)) => return Err(io::Error::new(io::ErrorKind::Other, e)),
+                None => return Ok(0),
+            }
+        }
+    }
+}
+
+/// A list of buffers treated as one logical, contiguous buffer.
+///
+/// Keeping the segments separate avoids copying them into one allocation
+/// just to satisfy `bytes::Buf`; `advance` simply drops fully-consumed
+/// segments off the front as they're read.
+#[derive(Clone)]
+struct BufList<T> {
+    bufs: VecDeque<T>,
+}
+
+impl<T: Buf> BufList<T> {
+    /// Drops empty buffers so `remaining() > 0` always implies `bytes()`
+    /// is non-empty, per `bytes::Buf`'s contract.
+    fn new(bufs: VecDeque<T>) -> BufList<T> {
+        BufList {
+            bufs: bufs.into_iter().filter(|buf| buf.remaining() > 0).collect(),
+        }
+    }
+}
+
+impl<T: Buf> Buf for BufList<T> {
+    fn remaining(&self) -> usize {
+        self.bufs.iter().map(Buf::remaining).sum()
+    }
+
+    fn bytes(&self) -> &[u8] {
+        self.bufs.front().map(Buf::bytes).unwrap_or(&[])
+    }
+
+    fn advance(&mut self, mut cnt: usize) {
+        while cnt > 0 {
+            let front_remaining = match self.bufs.front() {
+                Some(buf) => buf.remaining(),
+                None => break,
+            };
+
+            if cnt < front_remaining {
+                self.bufs.front_mut().unwrap().advance(cnt);
+                break;
+            }
+
+            cnt -= front_remaining;
+            self.bufs.pop_front();
+        }
+    }
+}
+
+/// Minimal IETF ChaCha20 (RFC 8439) stream cipher state.
+///
+/// Keeps the 32-bit block counter and the current position within the
+/// 64-byte keystream block, so it can keep encrypting/decrypting across
+/// however the upstream `Body` happens to chunk its data.
+struct ChaCha20 {
+    key: [u32; 8],
+    nonce: [u32; 3],
+    counter: u32,
+    keystream: [u8; 64],
+    pos: usize,
+}
+
+impl ChaCha20 {
+    fn new(key: [u8; 32], nonce: [u8; 12]) -> ChaCha20 {
+        let mut key_words = [0u32; 8];
+        for (word, chunk) in key_words.iter_mut().zip(key.chunks(4)) {
+            *word = read_u32_le(chunk);
+        }
+
+        let mut nonce_words = [0u32; 3];
+        for (word, chunk) in nonce_words.iter_mut().zip(nonce.chunks(4)) {
+            *word = read_u32_le(chunk);
+        }
+
+        ChaCha20 {
+            key: key_words,
+            nonce: nonce_words,
+            counter: 0,
+            keystream: [0; 64],
+            // Force a block to be generated before the first byte is used.
+            pos: 64,
+        }
+    }
+
+    fn generate_block(&mut self) {
+        const CONSTANTS: [u32; 4] = [0x6170_7865, 0x3320_646e, 0x7962_2d32, 0x6b20_6574];
+
+        let mut state = [0u32; 16];
+        state[0..4].copy_from_slice(&CONSTANTS);
+        state[4..12].copy_from_slice(&self.key);
+        state[12] = self.counter;
+        state[13..16].copy_from_slice(&self.nonce);
+
+        let mut working = state;
+        for _ in 0..10 {
+            quarter_round(&mut working, 0, 4, 8, 12);
+            quarter_round(&mut working, 1, 5, 9, 13);
+            quarter_round(&mut working, 2, 6, 10, 14);
+            quarter_round(&mut working, 3, 7, 11, 15);
+            quarter_round(&mut working, 0, 5, 10, 15);
+            quarter_round(&mut working, 1, 6, 11, 12);
+            quarter_round(&mut working, 2, 7, 8, 13);
+            quarter_round(&mut working, 3, 4, 9, 14);
+        }
+
+        for (word, initial) in working.iter_mut().zip(state.iter()) {
+            *word = word.wrapping_add(*initial);
+        }
+
+        for (out, word) in self.keystream.chunks_mut(4).zip(working.iter()) {
+            write_u32_le(out, *word);
+        }
+
+        self.counter = self.counter.wrapping_add(1);
+        self.pos = 0;
+    }
+
+    /// XOR `data` in place with the keystream, advancing the block counter
+    /// as needed. Used for both encryption and decryption.
+    fn apply_keystream(&mut self, data: &mut [u8]) {
+        for byte in data.iter_mut() {
+            if self.pos == self.keystream.len() {
+                self.generate_block();
+            }
+            *byte ^= self.keystream[self.pos];
+            self.pos += 1;
+        }
+    }
+}
+
+fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] ^= state[a];
+    state[d] = state[d].rotate_left(16);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] ^= state[c];
+    state[b] = state[b].rotate_left(12);
+
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] ^= state[a];
+    state[d] = state[d].rotate_left(8);
+
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] ^= state[c];
+    state[b] = state[b].rotate_left(7);
+}
+
+fn read_u32_le(bytes: &[u8]) -> u32 {
+    (bytes[0] as u32)
+        | (bytes[1] as u32) << 8
+        | (bytes[2] as u32) << 16
+        | (bytes[3] as u32) << 24
+}
+
+fn write_u32_le(bytes: &mut [u8], word: u32) {
+    bytes[0] = word as u8;
+    bytes[1] = (word >> 8) as u8;
+    bytes[2] = (word >> 16) as u8;
+    bytes[3] = (word >> 24) as u8;
+}
+
 impl fmt::Debug for Body {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Body")
@@ -200,3 +571,175 @@ impl fmt::Debug for Chunk {
         fmt::Debug::fmt(&self.inner, f)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // RFC 8439 section 2.3.2, the block function test vector.
+    #[test]
+    fn chacha20_block_matches_rfc8439_test_vector() {
+        let key: [u8; 32] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+            0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+            0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let nonce: [u8; 12] = [
+            0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00,
+        ];
+
+        let mut cipher = ChaCha20::new(key, nonce);
+        cipher.counter = 1;
+        cipher.generate_block();
+
+        let expected: [u8; 64] = [
+            0x10, 0xf1, 0xe7, 0xe4, 0xd1, 0x3b, 0x59, 0x15,
+            0x50, 0x0f, 0xdd, 0x1f, 0xa3, 0x20, 0x71, 0xc4,
+            0xc7, 0xd1, 0xf4, 0xc7, 0x33, 0xc0, 0x68, 0x03,
+            0x04, 0x22, 0xaa, 0x9a, 0xc3, 0xd4, 0x6c, 0x4e,
+            0xd2, 0x82, 0x64, 0x46, 0x07, 0x9f, 0xaa, 0x09,
+            0x14, 0xc2, 0xd7, 0x05, 0xd9, 0x8b, 0x02, 0xa2,
+            0xb5, 0x12, 0x9c, 0xd1, 0xde, 0x16, 0x4e, 0xb9,
+            0xcb, 0xd0, 0x83, 0xe8, 0xa2, 0x50, 0x3c, 0x4e,
+        ];
+        assert_eq!(&cipher.keystream[..], &expected[..]);
+    }
+
+    // RFC 8439 section 2.4.2, the full encryption test vector (initial
+    // block counter of 1).
+    #[test]
+    fn chacha20_encrypt_matches_rfc8439_test_vector() {
+        let key: [u8; 32] = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07,
+            0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+            0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16, 0x17,
+            0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e, 0x1f,
+        ];
+        let nonce: [u8; 12] = [
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x4a, 0x00, 0x00, 0x00, 0x00,
+        ];
+        let plaintext = b"Ladies and Gentlemen of the class of '99: \
+If I could offer you only one tip for the future, sunscreen would be it.";
+
+        let mut cipher = ChaCha20::new(key, nonce);
+        cipher.counter = 1;
+
+        let mut ciphertext = plaintext.to_vec();
+        cipher.apply_keystream(&mut ciphertext);
+
+        let expected_prefix: [u8; 16] = [
+            0x6e, 0x2e, 0x35, 0x9a, 0x25, 0x68, 0xf9, 0x80,
+            0x41, 0xba, 0x07, 0x28, 0xdd, 0x0d, 0x69, 0x81,
+        ];
+        assert_eq!(&ciphertext[..16], &expected_prefix[..]);
+
+        // Decryption (re-applying the same keystream) recovers the
+        // plaintext, across an arbitrary, non-64-byte chunk boundary.
+        let mut cipher = ChaCha20::new(key, nonce);
+        cipher.counter = 1;
+        let mut roundtrip = ciphertext.clone();
+        for chunk in roundtrip.chunks_mut(7) {
+            cipher.apply_keystream(chunk);
+        }
+        assert_eq!(&roundtrip[..], &plaintext[..]);
+    }
+
+    #[test]
+    fn wrap_stream_has_no_content_length_and_streams_converted_items() {
+        let stream = ::futures::stream::iter_ok::<_, ::std::io::Error>(vec!["foo", "bar"]);
+        let body = Body::wrap_stream(stream);
+        assert_eq!(body.content_length(), None);
+
+        let mut chunks = Vec::new();
+        for item in body.wait() {
+            match item {
+                Ok(chunk) => chunks.push(chunk),
+                Err(_) => panic!("unexpected stream error"),
+            }
+        }
+
+        let all: Vec<u8> = chunks.iter().flat_map(|c| c.as_ref().to_vec()).collect();
+        assert_eq!(all, b"foobar");
+    }
+
+    #[test]
+    fn from_segments_sums_content_length_and_skips_empty_segments() {
+        let body = Body::from_segments(vec![
+            Bytes::from_static(b"ab"),
+            Bytes::new(),
+            Bytes::from_static(b"cde"),
+        ]);
+        assert_eq!(body.content_length(), Some(5));
+
+        let mut chunks = Vec::new();
+        for item in body.wait() {
+            match item {
+                Ok(chunk) => chunks.push(chunk),
+                Err(_) => panic!("unexpected stream error"),
+            }
+        }
+
+        assert_eq!(chunks.len(), 2, "the empty segment must not surface as its own chunk");
+        let all: Vec<u8> = chunks.iter().flat_map(|c| c.as_ref().to_vec()).collect();
+        assert_eq!(all, b"abcde");
+    }
+
+    #[test]
+    fn into_hyper_clones_multi_body_for_retry_without_consuming_the_send_path() {
+        let body = Body::from_segments(vec![Bytes::from_static(b"ab"), Bytes::from_static(b"cde")]);
+        let (retry, _hyper_body) = body.into_hyper();
+        let retry = retry.expect("a Multi body should report itself as retryable");
+
+        assert_eq!(retry.content_length(), Some(5));
+        let mut chunks = Vec::new();
+        for item in retry.wait() {
+            match item {
+                Ok(chunk) => chunks.push(chunk),
+                Err(_) => panic!("unexpected stream error"),
+            }
+        }
+        let all: Vec<u8> = chunks.iter().flat_map(|c| c.as_ref().to_vec()).collect();
+        assert_eq!(all, b"abcde");
+    }
+
+    #[test]
+    fn into_reader_reads_across_chunks_and_hits_eof() {
+        let body = Body::from_segments(vec![
+            Bytes::from_static(b"hello "),
+            Bytes::from_static(b"world"),
+        ]);
+        let mut reader = body.into_reader();
+
+        let mut out = Vec::new();
+        let mut buf = [0u8; 4];
+        loop {
+            let n = reader.read(&mut buf).expect("read should not fail");
+            if n == 0 {
+                break;
+            }
+            out.extend_from_slice(&buf[..n]);
+        }
+        assert_eq!(out, b"hello world");
+
+        // Reading past EOF keeps returning `Ok(0)`.
+        assert_eq!(reader.read(&mut buf).expect("read past EOF"), 0);
+        assert_eq!(reader.read(&mut buf).expect("read past EOF again"), 0);
+    }
+
+    #[test]
+    fn into_reader_propagates_stream_errors_as_io_errors() {
+        let stream = ::futures::stream::iter_result(vec![
+            Ok(Chunk::from(Bytes::from_static(b"ok"))),
+            Err(::std::io::Error::new(::std::io::ErrorKind::Other, "boom")),
+        ]);
+        let mut reader = Body::wrap_stream(stream).into_reader();
+
+        let mut buf = [0u8; 2];
+        assert_eq!(reader.read(&mut buf).expect("first read succeeds"), 2);
+        assert_eq!(&buf, b"ok");
+
+        let err = reader.read(&mut buf).expect_err("the stream error should surface");
+        assert_eq!(err.kind(), ::std::io::ErrorKind::Other);
+    }
+}